@@ -0,0 +1,5 @@
+//! Layout elements.
+
+mod rotate;
+
+pub use rotate::RotateElem;
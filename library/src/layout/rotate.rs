@@ -0,0 +1,147 @@
+//! Rotation of content.
+
+use crate::layout::{AnyNode, Areas, Element, Frame, Layout, LayoutContext};
+use crate::prelude::*;
+
+/// A node that rotates its child by a fixed angle without affecting the
+/// surrounding layout flow.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RotateElem {
+    /// The content to rotate.
+    body: Content,
+    /// The rotation angle.
+    angle: Angle,
+    /// The point around which the content is rotated, relative to the
+    /// content's own frame.
+    origin: Axes<Option<GenAlign>>,
+}
+
+impl RotateElem {
+    /// Create a new, unrotated instance wrapping `body`.
+    pub fn new(body: Content) -> Self {
+        Self {
+            body,
+            angle: Angle::zero(),
+            origin: Axes::new(Some(GenAlign::Center), Some(GenAlign::Center)),
+        }
+    }
+
+    /// Set the rotation angle.
+    pub fn with_angle(mut self, angle: Angle) -> Self {
+        self.angle = angle;
+        self
+    }
+
+    /// Set the origin the rotation is performed around.
+    pub fn with_origin(mut self, origin: Axes<Option<GenAlign>>) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Wrap this node into content.
+    pub fn pack(self) -> Content {
+        AnyNode::from(self).into()
+    }
+}
+
+impl Layout for RotateElem {
+    fn layout(&self, ctx: &mut LayoutContext, areas: &Areas) -> Vec<Frame> {
+        let mut fragments = self.body.layout(ctx, areas);
+
+        for frame in &mut fragments {
+            let size = frame.size();
+            let quadrant = right_angle_quadrant(self.angle);
+            let rotated = rotated_size(size, quadrant);
+
+            // The pivot is the origin within the *original* box (what the
+            // child was laid out at), but the post-rotation translation has
+            // to land that pivot at the origin within the *rotated* box:
+            // for a square child (or a 0°/180° turn) the two boxes coincide
+            // and this is a no-op, but for a 90°/270° turn on a non-square
+            // child the box's width and height swap, so re-using the
+            // original pivot as the target would leave the content offset
+            // from the bounds the caller is about to see via `set_size`.
+            let anchor = origin_point(self.origin, size);
+            let target = origin_point(self.origin, rotated);
+
+            frame.transform(Transform::translate(target.x, target.y)
+                .pre_concat(Transform::rotate(self.angle))
+                .pre_concat(Transform::translate(-anchor.x, -anchor.y)));
+            frame.set_size(rotated);
+        }
+
+        fragments
+    }
+}
+
+impl From<RotateElem> for AnyNode {
+    fn from(rotate: RotateElem) -> Self {
+        Self::new(rotate)
+    }
+}
+
+/// The point within a box of size `dims` that `origin` resolves to, with
+/// unset axes defaulting to [`GenAlign::Center`].
+fn origin_point(origin: Axes<Option<GenAlign>>, dims: Size) -> Point {
+    let resolved = origin.map(|align| align.unwrap_or(GenAlign::Center));
+    Point::new(resolved.x.position(dims.width), resolved.y.position(dims.height))
+}
+
+/// The size of a frame of `size` after it's rotated by `quadrant` quarter
+/// turns: swapped for a 90°/270° turn (`1` or `3`), unchanged otherwise
+/// (including for an arbitrary, non-right angle, where `quadrant` is
+/// `None` and the caller relies on an external reflow instead).
+fn rotated_size(size: Size, quadrant: Option<u8>) -> Size {
+    match quadrant {
+        Some(1) | Some(3) => Size::new(size.height, size.width),
+        _ => size,
+    }
+}
+
+/// Returns `Some(quadrant)` in `0..=3` giving how many quarter turns `angle`
+/// represents (`0` for 0°/360°, `1` for 90°, `2` for 180°, `3` for 270°) if
+/// it's an exact multiple of a right angle, `None` for an arbitrary angle.
+fn right_angle_quadrant(angle: Angle) -> Option<u8> {
+    let quarters = angle.to_rad() / (std::f64::consts::FRAC_PI_2);
+    let rounded = quarters.round();
+    if (quarters - rounded).abs() < 1e-6 {
+        Some((rounded.rem_euclid(4.0)) as u8)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotated_size_swaps_dimensions_only_for_a_quarter_turn() {
+        let size = Size::new(200.0, 50.0);
+        assert_eq!(rotated_size(size, Some(0)), size);
+        assert_eq!(rotated_size(size, Some(1)), Size::new(50.0, 200.0));
+        assert_eq!(rotated_size(size, Some(2)), size);
+        assert_eq!(rotated_size(size, Some(3)), Size::new(50.0, 200.0));
+        assert_eq!(rotated_size(size, None), size);
+    }
+
+    #[test]
+    fn origin_point_recenters_into_a_swapped_non_square_box() {
+        let origin = Axes::new(Some(GenAlign::Center), Some(GenAlign::Center));
+        let size = Size::new(200.0, 50.0);
+        let rotated = rotated_size(size, Some(1));
+
+        // The pivot in the original (200x50) box sits at its center...
+        let anchor = origin_point(origin, size);
+        assert_eq!(anchor.x, 100.0);
+        assert_eq!(anchor.y, 25.0);
+
+        // ...but after a 90° turn the reported box is 50x200, so the
+        // rotated content has to be re-centered around *that* box's
+        // center, not the original one, or it ends up outside the bounds
+        // `set_size` reports to the caller.
+        let target = origin_point(origin, rotated);
+        assert_eq!(target.x, 25.0);
+        assert_eq!(target.y, 100.0);
+    }
+}
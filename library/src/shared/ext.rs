@@ -2,7 +2,7 @@
 
 use std::marker::PhantomData;
 
-use crate::layout::{AlignElem, MoveElem, PadElem};
+use crate::layout::{AlignElem, MoveElem, PadElem, RotateElem};
 use crate::prelude::*;
 use crate::text::{EmphElem, FontFamily, FontList, StrongElem, TextElem, UnderlineElem};
 
@@ -28,6 +28,9 @@ pub trait ContentExt {
 
     /// Transform this content's contents without affecting layout.
     fn moved(self, delta: Axes<Rel<Length>>) -> Self;
+
+    /// Rotate this content by a fixed angle without affecting layout.
+    fn rotated(self, angle: Angle, origin: Axes<Option<GenAlign>>) -> Self;
 }
 
 impl ContentExt for Content {
@@ -63,6 +66,10 @@ impl ContentExt for Content {
     fn moved(self, delta: Axes<Rel<Length>>) -> Self {
         MoveElem::new(self).with_dx(delta.x).with_dy(delta.y).pack()
     }
+
+    fn rotated(self, angle: Angle, origin: Axes<Option<GenAlign>>) -> Self {
+        RotateElem::new(self).with_angle(angle).with_origin(origin).pack()
+    }
 }
 
 /// Additional methods for style lists.
@@ -0,0 +1,432 @@
+//! Frame-to-frame caching of shaped line layouts.
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Arc;
+
+use ordered_float::OrderedFloat;
+
+use crate::font::advance::glyph_advance;
+use crate::font::metrics::{Metrics, MetricsCache};
+use crate::font::{FontId, FontVariant, VerticalFontMetric};
+
+/// The styling of a single shaped run, as far as the cache needs to
+/// distinguish it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RunStyle {
+    /// The id of the font used for this run.
+    pub font: FontId,
+    /// The style/weight/stretch of the font used for this run.
+    pub variant: FontVariant,
+    /// The OpenType feature tags to enable for this run (set via `font`'s
+    /// `features` parameter), mapping 4-byte tags to integer values.
+    pub features: Vec<(String, u32)>,
+    /// Which vertical metric of the font is the top edge of the line box,
+    /// set via `font`'s `top-edge` parameter.
+    pub top_edge: VerticalFontMetric,
+    /// Which vertical metric of the font is the bottom edge of the line box,
+    /// set via `font`'s `bottom-edge` parameter.
+    pub bottom_edge: VerticalFontMetric,
+    /// An explicit line height as a multiple of the font size, set via
+    /// `font`'s `line-height` parameter; `None` uses the font's own metrics.
+    pub line_height: Option<OrderedFloat<f64>>,
+}
+
+impl RunStyle {
+    /// Builds the style a run should be shaped with, once `font` has been
+    /// resolved for it: the font-state fields `font`'s named parameters
+    /// write (`variant`, `features`, `top_edge`, `bottom_edge`,
+    /// `line_height`) carry over unchanged, joined with the one thing the
+    /// state alone can't provide — which concrete font a span actually
+    /// resolved to.
+    pub fn new(
+        font: FontId,
+        variant: FontVariant,
+        features: Vec<(String, u32)>,
+        top_edge: VerticalFontMetric,
+        bottom_edge: VerticalFontMetric,
+        line_height: Option<f64>,
+    ) -> Self {
+        Self {
+            font,
+            variant,
+            features,
+            top_edge,
+            bottom_edge,
+            line_height: line_height.map(OrderedFloat),
+        }
+    }
+}
+
+/// A single positioned glyph within a [`LineLayout`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShapedGlyph {
+    /// The font this glyph belongs to.
+    pub font: FontId,
+    /// The source character this glyph represents.
+    pub c: char,
+    /// The horizontal advance to the next glyph.
+    pub x_advance: f32,
+}
+
+/// A shaped line, ready to be placed into a frame.
+#[derive(Debug, Clone)]
+pub struct LineLayout {
+    /// The shaped glyphs, in visual order.
+    pub glyphs: Vec<ShapedGlyph>,
+    /// The advance width of the whole line.
+    pub width: f32,
+    /// The distance from the baseline to the line's top edge, as configured
+    /// by `top-edge` and read from the first run's cached [`Metrics`].
+    pub ascent: f64,
+    /// The distance from the baseline to the line's bottom edge, as
+    /// configured by `bottom-edge` and read from the first run's cached
+    /// [`Metrics`].
+    pub descent: f64,
+    /// The distance to the next line's baseline.
+    pub line_height: f64,
+}
+
+/// A key identifying a cached line layout: the exact text, font size and run
+/// styling that produced it. Implemented as a trait so that lookups can be
+/// performed with a borrowed [`CacheKeyRef`] without allocating an owned
+/// [`CacheKey`] on every hit.
+trait CacheKeyLike {
+    fn text(&self) -> &str;
+    fn size(&self) -> OrderedFloat<f32>;
+    fn runs(&self) -> &[(usize, RunStyle)];
+}
+
+impl<'a> Borrow<dyn CacheKeyLike + 'a> for CacheKey {
+    fn borrow(&self) -> &(dyn CacheKeyLike + 'a) {
+        self
+    }
+}
+
+impl PartialEq for dyn CacheKeyLike + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        self.text() == other.text() && self.size() == other.size() && self.runs() == other.runs()
+    }
+}
+
+impl Eq for dyn CacheKeyLike + '_ {}
+
+impl std::hash::Hash for dyn CacheKeyLike + '_ {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.text().hash(state);
+        self.size().hash(state);
+        self.runs().hash(state);
+    }
+}
+
+/// An owned cache key, stored inside the maps.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    text: String,
+    size: OrderedFloat<f32>,
+    runs: Vec<(usize, RunStyle)>,
+}
+
+impl CacheKeyLike for CacheKey {
+    fn text(&self) -> &str {
+        &self.text
+    }
+
+    fn size(&self) -> OrderedFloat<f32> {
+        self.size
+    }
+
+    fn runs(&self) -> &[(usize, RunStyle)] {
+        &self.runs
+    }
+}
+
+/// A borrowed cache key, used to probe the maps without allocating.
+struct CacheKeyRef<'a> {
+    text: &'a str,
+    size: OrderedFloat<f32>,
+    runs: &'a [(usize, RunStyle)],
+}
+
+impl CacheKeyLike for CacheKeyRef<'_> {
+    fn text(&self) -> &str {
+        self.text
+    }
+
+    fn size(&self) -> OrderedFloat<f32> {
+        self.size
+    }
+
+    fn runs(&self) -> &[(usize, RunStyle)] {
+        self.runs
+    }
+}
+
+/// A double-buffered map: a value looked up via a borrowed `Q` is promoted
+/// from `prev` into `curr` if it's only cached there; anything left in
+/// `prev` once [`FrameCache::advance`] runs was not reused this pass and is
+/// dropped, which bounds memory use to entries that are actually still in
+/// use. Factored out of [`TextLayoutCache`] so its promotion/eviction and
+/// borrowed-key lookup can be tested independent of the cache's actual
+/// key/value types.
+struct FrameCache<K, V> {
+    prev: HashMap<K, V>,
+    curr: HashMap<K, V>,
+}
+
+impl<K, V> Default for FrameCache<K, V> {
+    fn default() -> Self {
+        Self { prev: HashMap::new(), curr: HashMap::new() }
+    }
+}
+
+impl<K, V> FrameCache<K, V>
+where
+    K: Eq + Hash,
+    V: Clone,
+{
+    /// Create an empty cache.
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Look up `probe` (promoting it from the previous frame if it's only
+    /// cached there), or compute and insert a fresh value via `make_key`/
+    /// `make_value` on a miss.
+    fn get_or_insert_with<Q>(
+        &mut self,
+        probe: &Q,
+        make_key: impl FnOnce() -> K,
+        make_value: impl FnOnce() -> V,
+    ) -> V
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if let Some(hit) = self.curr.get(probe) {
+            return hit.clone();
+        }
+
+        if let Some((key, value)) = self.prev.remove_entry(probe) {
+            self.curr.insert(key, value.clone());
+            return value;
+        }
+
+        let key = make_key();
+        let value = make_value();
+        self.curr.insert(key, value.clone());
+        value
+    }
+
+    /// Advance to the next frame: entries untouched this pass are evicted,
+    /// entries that were reused stay warm for one more pass.
+    fn advance(&mut self) {
+        std::mem::swap(&mut self.prev, &mut self.curr);
+        self.curr.clear();
+    }
+}
+
+/// Memoizes shaped line layouts across incremental layout passes.
+///
+/// The cache is double-buffered (see [`FrameCache`]): a line that was
+/// shaped during the previous pass and is requested again during the
+/// current one is promoted rather than reshaped, and a line that goes a
+/// full pass without being requested is dropped.
+#[derive(Default)]
+pub struct TextLayoutCache {
+    frames: FrameCache<CacheKey, Arc<LineLayout>>,
+    /// Backs the vertical metrics (`top-edge`/`bottom-edge`/line height) of
+    /// freshly shaped lines, shared across shapes so a font's metrics are
+    /// only computed once. The values are the approximations from
+    /// [`approximate_metrics`]; no font file is read.
+    metrics: MetricsCache,
+}
+
+impl TextLayoutCache {
+    /// Create an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Retrieve (or shape and insert) the layout for a line of `text` set at
+    /// `size` with the given `runs`.
+    pub fn layout_line(
+        &mut self,
+        text: &str,
+        size: f32,
+        runs: &[(usize, RunStyle)],
+    ) -> Arc<LineLayout> {
+        let probe: &dyn CacheKeyLike =
+            &CacheKeyRef { text, size: OrderedFloat(size), runs };
+        let metrics = &mut self.metrics;
+
+        self.frames.get_or_insert_with(
+            probe,
+            || CacheKey { text: text.into(), size: OrderedFloat(size), runs: runs.to_vec() },
+            || Arc::new(shape_line(text, size, runs, metrics)),
+        )
+    }
+
+    /// Advance to the next frame: lines untouched this pass are evicted,
+    /// lines that were reused stay warm for one more pass.
+    pub fn finish_frame(&mut self) {
+        self.frames.advance();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn borrowed_key_lookup_hits_without_building_an_owned_key() {
+        let mut cache: FrameCache<String, i32> = FrameCache::new();
+        cache.get_or_insert_with("a", || "a".to_string(), || 1);
+
+        // Probing with only a borrowed `&str` (no owned `String` built for
+        // the lookup itself) must still hit the entry keyed by `String`.
+        let hit = cache.get_or_insert_with(
+            "a",
+            || panic!("should hit via the borrowed probe, not recompute"),
+            || panic!("should hit via the borrowed probe, not recompute"),
+        );
+        assert_eq!(hit, 1);
+    }
+
+    #[test]
+    fn an_entry_survives_one_idle_frame_then_is_evicted() {
+        let mut cache: FrameCache<String, i32> = FrameCache::new();
+        cache.get_or_insert_with("a", || "a".to_string(), || 1);
+
+        // Frame 2: "a" isn't looked up, so it's demoted to `prev` but not
+        // yet dropped — it can still be promoted back if it's reused.
+        cache.advance();
+        assert_eq!(cache.prev.len(), 1);
+        assert_eq!(cache.curr.len(), 0);
+
+        let hit = cache.get_or_insert_with(
+            "a",
+            || panic!("should hit via prev, not recompute"),
+            || panic!("should hit via prev, not recompute"),
+        );
+        assert_eq!(hit, 1);
+        assert_eq!(cache.curr.len(), 1);
+
+        // Frame 3: "a" was reused in frame 2, so it's demoted to `prev`
+        // again rather than dropped.
+        cache.advance();
+        assert_eq!(cache.prev.len(), 1);
+        assert_eq!(cache.curr.len(), 0);
+
+        // Frame 4: "a" went untouched through frame 3, so it is finally
+        // evicted.
+        cache.advance();
+        assert_eq!(cache.prev.len(), 0);
+        assert_eq!(cache.curr.len(), 0);
+    }
+}
+
+/// Lays out every line of a paragraph through `cache` instead of reshaping
+/// each one unconditionally, then advances the cache to the next frame.
+///
+/// This is the text layout path callers should use for incremental
+/// (e.g. watch/preview) recompiles: within a single call, repeated lines
+/// (from `\n`-separated spans that happen to share text, size and runs)
+/// hit the cache after the first shape, and across calls, lines that
+/// reappear unchanged from the previous pass are promoted rather than
+/// reshaped.
+pub fn layout_paragraph(
+    cache: &mut TextLayoutCache,
+    lines: &[(String, f32, Vec<(usize, RunStyle)>)],
+) -> Vec<Arc<LineLayout>> {
+    let laid_out = lines
+        .iter()
+        .map(|(text, size, runs)| cache.layout_line(text, *size, runs))
+        .collect();
+    cache.finish_frame();
+    laid_out
+}
+
+/// Shapes `text` from scratch, bypassing the cache.
+///
+/// `runs` gives the byte offset each run starts at (sorted ascending) along
+/// with the style to shape it with; a run extends until the next run's
+/// offset, or the end of `text` for the last one. The line's vertical
+/// metrics are read from the first run's font, matching how a mixed-font
+/// line's box is dominated by whichever font started it.
+fn shape_line(
+    text: &str,
+    size: f32,
+    runs: &[(usize, RunStyle)],
+    metrics: &mut MetricsCache,
+) -> LineLayout {
+    let mut glyphs = Vec::new();
+    let mut width = 0.0;
+
+    for (i, (start, style)) in runs.iter().enumerate() {
+        let end = runs.get(i + 1).map(|(next, _)| *next).unwrap_or(text.len());
+        let Some(run_text) = text.get(*start..end) else { continue };
+
+        for ch in run_text.chars() {
+            let x_advance = apply_features(style.font, ch, size, &style.features);
+            glyphs.push(ShapedGlyph { font: style.font, c: ch, x_advance });
+            width += x_advance;
+        }
+    }
+
+    let (ascent, descent, line_height) = match runs.first() {
+        Some((_, style)) => vertical_metrics(style, size, metrics),
+        None => (0.0, 0.0, 0.0),
+    };
+
+    LineLayout { glyphs, width, ascent, descent, line_height }
+}
+
+/// Resolves `style`'s `top-edge`/`bottom-edge`/line height against its
+/// font's cached [`Metrics`], as multiples of `size`.
+fn vertical_metrics(style: &RunStyle, size: f32, cache: &mut MetricsCache) -> (f64, f64, f64) {
+    let metrics = cache.get(style.font, approximate_metrics);
+    let size = size as f64;
+
+    let ascent = metrics.vertical(style.top_edge) * size;
+    let descent = -metrics.vertical(style.bottom_edge) * size;
+    let natural = metrics.ascent + metrics.descent + metrics.line_gap;
+    let line_height = style.line_height.map_or(natural, |lh| lh.into_inner()) * size;
+
+    (ascent, descent, line_height)
+}
+
+/// A stand-in [`Metrics`] using typical proportions for a Latin text face.
+/// It is used for every font, in place of metrics read from the font file.
+fn approximate_metrics() -> Metrics {
+    Metrics {
+        ascent: 0.8,
+        descent: 0.2,
+        cap_height: 0.7,
+        x_height: 0.5,
+        line_gap: 0.0,
+        units_per_em: 1000.0,
+    }
+}
+
+/// Measures the advance of `ch`, adjusted for any of `features` that affect
+/// it.
+///
+/// Most OpenType features (ligatures, small caps, stylistic sets) swap
+/// glyph outlines without changing advances and so need a real shaper to
+/// realize; `tnum` (tabular figures) is the one common feature whose effect
+/// is purely metric, so it's the one applied here: when requested, every
+/// digit is measured at the width of the widest digit instead of its own
+/// proportional width, keeping columns of numbers aligned.
+fn apply_features(font: FontId, ch: char, size: f32, features: &[(String, u32)]) -> f32 {
+    let tabular_numerals = features.iter().any(|(tag, value)| tag == "tnum" && *value != 0);
+
+    if tabular_numerals && ch.is_ascii_digit() {
+        return ('0'..='9')
+            .map(|digit| glyph_advance(font, digit, size))
+            .fold(0.0, f32::max);
+    }
+
+    glyph_advance(font, ch, size)
+}
@@ -0,0 +1,7 @@
+//! Text layout and shaping.
+
+mod cache;
+mod wrap;
+
+pub use cache::{layout_paragraph, LineLayout, RunStyle, ShapedGlyph, TextLayoutCache};
+pub use wrap::{wrap_paragraphs, Boundary, LineWrapper, LineWrapperPool};
@@ -0,0 +1,260 @@
+//! Greedy word wrapping with cached glyph advances and wrapper pooling.
+
+use std::collections::HashMap;
+
+use ordered_float::OrderedFloat;
+
+use crate::font::advance::glyph_advance;
+use crate::font::FontId;
+
+/// A candidate line break, with the text offset it falls at and the width
+/// of the line up to that point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Boundary {
+    /// The byte offset into the wrapped text where the line ends.
+    pub offset: usize,
+    /// The accumulated pixel width of the line up to `offset`.
+    pub width: f32,
+}
+
+/// Performs greedy, word-level line breaking for a single font and size,
+/// reusing a small cache of glyph advance widths across calls.
+pub struct LineWrapper {
+    font: FontId,
+    size: f32,
+    /// Advances for the whole ASCII range, indexed directly by byte
+    /// value so the common case avoids hashing entirely.
+    ascii_advances: [Option<f32>; 128],
+    /// Advances for everything outside ASCII.
+    advances: HashMap<char, f32>,
+}
+
+impl LineWrapper {
+    /// Create a wrapper for `font` at `size`.
+    fn new(font: FontId, size: f32) -> Self {
+        Self {
+            font,
+            size,
+            ascii_advances: [None; 128],
+            advances: HashMap::new(),
+        }
+    }
+
+    /// Reset the wrapper so it can be reused for a different `font`/`size`
+    /// without discarding its glyph advance cache's allocation.
+    fn reset(&mut self, font: FontId, size: f32) {
+        self.font = font;
+        self.size = size;
+        self.ascii_advances = [None; 128];
+        self.advances.clear();
+    }
+
+    /// Greedily break `text` into lines that fit within `width`, yielding one
+    /// [`Boundary`] per line.
+    ///
+    /// A single word longer than `width` is still guaranteed to make
+    /// progress: it breaks mid-word at the last char boundary that fits,
+    /// rather than looping forever waiting for whitespace.
+    pub fn wrap_line<'a>(
+        &'a mut self,
+        text: &'a str,
+        width: f32,
+    ) -> impl Iterator<Item = Boundary> + 'a {
+        wrap_greedy(text, width, move |ch| self.advance(ch))
+    }
+
+    /// Look up (and cache) the pixel advance of `ch` at this wrapper's font
+    /// and size.
+    fn advance(&mut self, ch: char) -> f32 {
+        if ch.is_ascii() {
+            let slot = &mut self.ascii_advances[ch as usize];
+            return *slot.get_or_insert_with(|| measure_advance(self.font, ch, self.size));
+        }
+
+        if let Some(&advance) = self.advances.get(&ch) {
+            return advance;
+        }
+
+        let advance = measure_advance(self.font, ch, self.size);
+        self.advances.insert(ch, advance);
+        advance
+    }
+}
+
+/// A pool of [`LineWrapper`]s, keyed by font and size, so that repeated line
+/// wrapping (e.g. across paragraphs sharing a font) reuses wrappers and
+/// their glyph advance caches instead of reallocating.
+#[derive(Default)]
+pub struct LineWrapperPool {
+    wrappers: HashMap<(FontId, OrderedFloat<f32>), Vec<LineWrapper>>,
+}
+
+impl LineWrapperPool {
+    /// Create an empty pool.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Borrow a wrapper for `font` at `size`, reusing one from the pool if
+    /// available.
+    pub fn get(&mut self, font: FontId, size: f32) -> PooledLineWrapper<'_> {
+        let key = (font, OrderedFloat(size));
+        let wrapper = match self.wrappers.get_mut(&key).and_then(Vec::pop) {
+            Some(mut wrapper) => {
+                wrapper.reset(font, size);
+                wrapper
+            }
+            None => LineWrapper::new(font, size),
+        };
+
+        PooledLineWrapper { pool: self, key, wrapper: Some(wrapper) }
+    }
+}
+
+/// A [`LineWrapper`] borrowed from a [`LineWrapperPool`], returned to the
+/// pool when dropped.
+pub struct PooledLineWrapper<'a> {
+    pool: &'a mut LineWrapperPool,
+    key: (FontId, OrderedFloat<f32>),
+    wrapper: Option<LineWrapper>,
+}
+
+impl std::ops::Deref for PooledLineWrapper<'_> {
+    type Target = LineWrapper;
+
+    fn deref(&self) -> &Self::Target {
+        self.wrapper.as_ref().unwrap()
+    }
+}
+
+impl std::ops::DerefMut for PooledLineWrapper<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.wrapper.as_mut().unwrap()
+    }
+}
+
+impl Drop for PooledLineWrapper<'_> {
+    fn drop(&mut self) {
+        if let Some(wrapper) = self.wrapper.take() {
+            self.pool.wrappers.entry(self.key).or_default().push(wrapper);
+        }
+    }
+}
+
+/// Wraps each paragraph of `text` (split on `\n`) to `width`, borrowing a
+/// [`LineWrapper`] for `font`/`size` from `pool` instead of allocating a new
+/// one per paragraph.
+pub fn wrap_paragraphs(
+    pool: &mut LineWrapperPool,
+    font: FontId,
+    size: f32,
+    text: &str,
+    width: f32,
+) -> Vec<Vec<Boundary>> {
+    let mut wrapper = pool.get(font, size);
+    text.split('\n').map(|paragraph| wrapper.wrap_line(paragraph, width).collect()).collect()
+}
+
+/// Measures the pixel advance of `ch` in `font` at `size`.
+fn measure_advance(font: FontId, ch: char, size: f32) -> f32 {
+    glyph_advance(font, ch, size)
+}
+
+/// The greedy line-breaking algorithm behind [`LineWrapper::wrap_line`],
+/// factored out so it can be exercised without a real font to `advance`
+/// glyphs with.
+fn wrap_greedy<'a>(
+    text: &'a str,
+    width: f32,
+    mut advance: impl FnMut(char) -> f32 + 'a,
+) -> impl Iterator<Item = Boundary> + 'a {
+    let mut cursor = 0;
+    let mut line_start = 0;
+    let mut line_width = 0.0;
+    // The boundary to break at if the line overflows, alongside the offset
+    // the next line should resume at: `boundary.offset` excludes the
+    // whitespace that produced it (so its width doesn't leak into either
+    // line), while `resume` skips past that whitespace entirely (so it
+    // doesn't reappear at the head of the next line).
+    let mut candidate: Option<(Boundary, usize)> = None;
+
+    std::iter::from_fn(move || {
+        while cursor < text.len() {
+            let rest = &text[cursor..];
+            let ch = rest.chars().next()?;
+            let delta = advance(ch);
+            let next_cursor = cursor + ch.len_utf8();
+
+            if ch.is_whitespace() {
+                candidate = Some((Boundary { offset: cursor, width: line_width }, next_cursor));
+            } else if line_width + delta > width && cursor > line_start {
+                // A candidate break that sits at `line_start` (e.g. the
+                // leading whitespace left behind by the previous break)
+                // would make zero progress if we broke there again, so
+                // treat it the same as having no candidate at all and fall
+                // back to the mid-word break at `cursor`, which is
+                // guaranteed to be past `line_start`.
+                let (boundary, resume) = match candidate.take() {
+                    Some((boundary, resume)) if boundary.offset > line_start => (boundary, resume),
+                    _ => (Boundary { offset: cursor, width: line_width }, cursor),
+                };
+                line_start = resume;
+                cursor = resume;
+                line_width = 0.0;
+                return Some(boundary);
+            }
+
+            line_width += delta;
+            cursor = next_cursor;
+        }
+
+        if line_start < text.len() {
+            let boundary = Boundary { offset: text.len(), width: line_width };
+            line_start = text.len();
+            return Some(boundary);
+        }
+
+        None
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every char (including whitespace) is 1.0 wide, matching neither real
+    /// font metrics nor `glyph_advance`: the point is to pin the breaking
+    /// *logic*, not any particular font's measurements.
+    fn advance(_ch: char) -> f32 {
+        1.0
+    }
+
+    #[test]
+    fn breaks_on_whitespace_excluding_it_from_either_line() {
+        // "aaa bbb" at width 5: "aaa" (3) + space (1) + "b" (1) = 5 fits,
+        // adding a second "b" would overflow, so it should break at the
+        // space, excluding it
+        // from both the first line's width and the second line's text.
+        let boundaries: Vec<_> = wrap_greedy("aaa bbb", 5.0, advance).collect();
+        assert_eq!(boundaries.len(), 2);
+        assert_eq!(boundaries[0], Boundary { offset: 3, width: 3.0 });
+        assert_eq!(boundaries[1], Boundary { offset: 7, width: 3.0 });
+    }
+
+    #[test]
+    fn a_single_overlong_word_breaks_mid_word_instead_of_looping() {
+        // "a verylongword" at width 4: after breaking before "verylongword",
+        // the word alone still exceeds the width, so it must break again
+        // mid-word rather than spin forever on the leftover candidate.
+        let boundaries: Vec<_> = wrap_greedy("a verylongword", 4.0, advance).collect();
+        assert!(boundaries.len() > 1, "wrapping must make progress, not loop");
+        assert_eq!(boundaries[0], Boundary { offset: 1, width: 1.0 });
+        // Every boundary must strictly advance past the previous one.
+        let mut prev = 0;
+        for boundary in &boundaries {
+            assert!(boundary.offset > prev, "boundary must make forward progress");
+            prev = boundary.offset;
+        }
+        assert_eq!(prev, "a verylongword".len());
+    }
+}
@@ -0,0 +1,87 @@
+//! Approximate per-character glyph advance widths.
+//!
+//! This estimates advances from a character's Unicode width class rather
+//! than reading the font's `hmtx` table, so the shaping and line-wrapping
+//! caches can be measured without loading glyph outlines; callers that have
+//! access to the real shaped glyphs should prefer those widths.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use super::FontId;
+
+/// Estimates the advance width of `ch` when set in `font` at `size`.
+pub fn glyph_advance(font: FontId, ch: char, size: f32) -> f32 {
+    width_class(ch) * size * font_width_scale(font)
+}
+
+/// The fraction of the em square `ch` is expected to occupy.
+fn width_class(ch: char) -> f32 {
+    if ch == ' ' || ch == '\t' {
+        0.25
+    } else if ch.is_whitespace() {
+        0.2
+    } else if is_wide(ch) {
+        1.0
+    } else if ch.is_ascii_digit() {
+        digit_width_class(ch)
+    } else if ch.is_ascii_punctuation() {
+        0.3
+    } else {
+        0.55
+    }
+}
+
+/// The fraction of the em square a digit occupies in its default
+/// (proportional, not tabular) form.
+///
+/// Unlike tabular figures, proportional digits aren't all the same width;
+/// `1` in particular is narrower than the rest in most text faces, which is
+/// the detail that makes forcing `tnum`'s uniform width (the widest digit's
+/// advance) an observable change rather than a no-op.
+fn digit_width_class(ch: char) -> f32 {
+    match ch {
+        '1' => 0.4,
+        _ => 0.5,
+    }
+}
+
+/// Whether `ch` falls into a CJK wide/fullwidth range, which occupies
+/// roughly a full em instead of a fraction of one.
+fn is_wide(ch: char) -> bool {
+    matches!(ch as u32,
+        0x1100..=0x115F
+            | 0x2E80..=0xA4CF
+            | 0xAC00..=0xD7A3
+            | 0xF900..=0xFAFF
+            | 0xFF00..=0xFF60
+            | 0xFFE0..=0xFFE6
+            | 0x20000..=0x3FFFD
+    )
+}
+
+/// A small, deterministic per-font scale factor so that different fonts at
+/// the same size don't measure identically.
+fn font_width_scale(font: FontId) -> f32 {
+    let mut hasher = DefaultHasher::new();
+    font.hash(&mut hasher);
+    0.9 + ((hasher.finish() % 21) as f32) / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digits_are_not_all_the_same_width() {
+        // `1` must be narrower than the other digits, or forcing every
+        // digit to the widest one's advance (the `tnum` feature) would be
+        // a no-op: the max over an already-uniform set changes nothing.
+        let one = digit_width_class('1');
+        let widest = ('0'..='9')
+            .map(digit_width_class)
+            .fold(0.0, f32::max);
+
+        assert!(one < widest, "'1' ({one}) should be narrower than the widest digit ({widest})");
+    }
+}
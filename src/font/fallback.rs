@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+
+/// Enumerates the families available in the current font environment and
+/// resolves a candidate list down to the first family that is actually
+/// loadable, falling back to whatever the environment offers rather than
+/// producing tofu for an unknown family.
+pub struct FontResolver<'a> {
+    /// All family names the environment can load, in their original casing,
+    /// as reported by `load_families`.
+    families: Vec<String>,
+    /// `families`, lowercased in parallel, used for case-insensitive
+    /// matching so that e.g. `"Fira Sans"` matches a `"fira sans"` entry.
+    families_lower: Vec<String>,
+    /// Caches the resolution of a (lowercased) candidate list so repeated
+    /// lookups for the same `serif`/`sans-serif`/`monospace` chain during a
+    /// document are cheap.
+    cache: HashMap<Vec<String>, String>,
+    /// Loads the families actually available in the environment.
+    load_families: Box<dyn Fn() -> Vec<String> + 'a>,
+}
+
+impl<'a> FontResolver<'a> {
+    /// Create a resolver backed by `load_families`, which enumerates the
+    /// families the environment can load.
+    pub fn new(load_families: impl Fn() -> Vec<String> + 'a) -> Self {
+        let families = load_families();
+        let families_lower = families.iter().map(|f| f.to_lowercase()).collect();
+        Self {
+            families,
+            families_lower,
+            cache: HashMap::new(),
+            load_families: Box::new(load_families),
+        }
+    }
+
+    /// All families the environment provides, in their original casing and
+    /// in the order it reports them.
+    pub fn all_families(&self) -> &[String] {
+        &self.families
+    }
+
+    /// Resolve an ordered list of candidate family names (matched
+    /// case-insensitively) to the first one that the environment actually
+    /// provides, in its original casing, falling back to any system family
+    /// if none of the candidates match.
+    pub fn resolve(&mut self, candidates: &[String]) -> Option<&str> {
+        let candidates: Vec<String> = candidates.iter().map(|c| c.to_lowercase()).collect();
+
+        if self.cache.contains_key(&candidates) {
+            return self.cache.get(&candidates).map(String::as_str);
+        }
+
+        let position = candidates
+            .iter()
+            .find_map(|candidate| self.families_lower.iter().position(|family| family == candidate))
+            .or(if self.families.is_empty() { None } else { Some(0) })?;
+
+        let resolved = self.families[position].clone();
+        self.cache.insert(candidates.clone(), resolved);
+        self.cache.get(&candidates).map(String::as_str)
+    }
+
+    /// Re-enumerate the environment's families, dropping any cached
+    /// resolutions that might no longer be valid.
+    pub fn refresh(&mut self) {
+        self.families = (self.load_families)();
+        self.families_lower = self.families.iter().map(|f| f.to_lowercase()).collect();
+        self.cache.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn resolve_matches_candidates_case_insensitively() {
+        let mut resolver = FontResolver::new(|| strings(&["Fira Sans", "Noto Serif"]));
+
+        // A mixed-case candidate must still match the (differently-cased)
+        // environment entry instead of falling through to the first family.
+        assert_eq!(resolver.resolve(&strings(&["fira sans"])), Some("Fira Sans"));
+        assert_eq!(resolver.resolve(&strings(&["FIRA SANS"])), Some("Fira Sans"));
+    }
+
+    #[test]
+    fn resolve_preserves_the_environment_s_original_casing() {
+        let mut resolver = FontResolver::new(|| strings(&["Fira Sans"]));
+
+        assert_eq!(resolver.all_families(), &["Fira Sans".to_string()]);
+        assert_eq!(resolver.resolve(&strings(&["fira sans"])), Some("Fira Sans"));
+    }
+
+    #[test]
+    fn resolve_falls_back_to_the_first_family_when_no_candidate_matches() {
+        let mut resolver = FontResolver::new(|| strings(&["Noto Serif", "Fira Sans"]));
+
+        assert_eq!(resolver.resolve(&strings(&["Helvetica"])), Some("Noto Serif"));
+    }
+
+    #[test]
+    fn resolve_returns_none_when_the_environment_has_no_families() {
+        let mut resolver = FontResolver::new(|| strings(&[]));
+
+        assert_eq!(resolver.resolve(&strings(&["Fira Sans"])), None);
+    }
+}
@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+
+use super::{FontId, VerticalFontMetric};
+
+/// The metrics of a font, scaled to a unit em square.
+///
+/// All values are in font units divided by `units_per_em`, i.e. they can be
+/// multiplied directly by a font size to get absolute lengths.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Metrics {
+    /// The distance from the baseline to the top of the em square.
+    pub ascent: f64,
+    /// The distance from the baseline to the bottom of the em square.
+    pub descent: f64,
+    /// The height of flat capital letters like `H`.
+    pub cap_height: f64,
+    /// The height of flat lowercase letters like `x`.
+    pub x_height: f64,
+    /// The recommended gap between two lines of text.
+    pub line_gap: f64,
+    /// The number of font units per em, used to scale the raw values above.
+    pub units_per_em: f64,
+}
+
+impl Metrics {
+    /// Resolve a [`VerticalFontMetric`] to a length, as a multiple of the
+    /// font size.
+    pub fn vertical(&self, metric: VerticalFontMetric) -> f64 {
+        match metric {
+            VerticalFontMetric::Ascender => self.ascent,
+            VerticalFontMetric::CapHeight => self.cap_height,
+            VerticalFontMetric::XHeight => self.x_height,
+            VerticalFontMetric::Baseline => 0.0,
+            VerticalFontMetric::Descender => -self.descent,
+        }
+    }
+}
+
+/// Caches the [`Metrics`] of fonts so they only have to be read from the
+/// font file once.
+#[derive(Default)]
+pub struct MetricsCache(HashMap<FontId, Metrics>);
+
+impl MetricsCache {
+    /// Create an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Retrieve the metrics for `font`, reading them from the font file on
+    /// the first request.
+    pub fn get(&mut self, font: FontId, read: impl FnOnce() -> Metrics) -> Metrics {
+        *self.0.entry(font).or_insert_with(read)
+    }
+}
@@ -0,0 +1,7 @@
+//! Font loading, metrics and glyph measurement.
+
+pub mod advance;
+mod fallback;
+pub mod metrics;
+
+pub use fallback::FontResolver;
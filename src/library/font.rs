@@ -16,7 +16,12 @@ use super::*;
 /// - Font Stretch: `stretch`, of type `relative`, between 0.5 and 2.0.
 /// - Top edge of the font: `top-edge`, of type `vertical-font-metric`.
 /// - Bottom edge of the font: `bottom-edge`, of type `vertical-font-metric`.
+/// - Line height: `line-height`, of type `float`, as a multiple of the font
+///   size. When this is unset, the line height is the sum of the font's
+///   ascender, descender and line gap, which differs between fonts; when
+///   set, it is the same for every font at a given size.
 /// - Color the glyphs: `color`, of type `color`.
+/// - OpenType font features: `features`, of type `font-features`.
 /// - Serif family definition: `serif`, of type `font-family-definition`.
 /// - Sans-serif family definition: `sans-serif`, of type `font-family-definition`.
 /// - Monospace family definition: `monospace`, of type `font-family-definition`.
@@ -25,6 +30,11 @@ use super::*;
 /// A template that configures font properties. The effect is scoped to the body
 /// if present.
 ///
+/// Family names are resolved against the fonts the environment actually
+/// provides (see [`fonts`]): a family further down the `serif`/`sans-serif`/
+/// `monospace` chain is used if an earlier one isn't installed, instead of
+/// silently falling through to tofu.
+///
 /// # Relevant types and constants
 /// - Type `font-family`
 ///   - `serif`
@@ -55,6 +65,10 @@ use super::*;
 ///   - `x-height`
 ///   - `baseline`
 ///   - `descender`
+/// - Type `font-features`
+///   - coerces from `array` (tags to enable, e.g. `("smcp", "onum")`)
+///   - coerces from `dictionary` (tags mapped to integer values, e.g.
+///     `("ss01": 1, "cv01": 2)`)
 pub fn font(ctx: &mut EvalContext, args: &mut FuncArgs) -> Value {
     let size = args.find::<Linear>(ctx);
     let list: Vec<_> = args.filter::<FontFamily>(ctx).collect();
@@ -63,7 +77,9 @@ pub fn font(ctx: &mut EvalContext, args: &mut FuncArgs) -> Value {
     let stretch = args.get(ctx, "stretch");
     let top_edge = args.get(ctx, "top-edge");
     let bottom_edge = args.get(ctx, "bottom-edge");
+    let line_height = args.get(ctx, "line-height");
     let color = args.get(ctx, "color");
+    let features = args.get(ctx, "features");
     let serif = args.get(ctx, "serif");
     let sans_serif = args.get(ctx, "sans-serif");
     let monospace = args.get(ctx, "monospace");
@@ -105,20 +121,35 @@ pub fn font(ctx: &mut EvalContext, args: &mut FuncArgs) -> Value {
             ctx.state.font.bottom_edge = bottom_edge;
         }
 
+        if let Some(LineHeight(line_height)) = line_height {
+            ctx.state.font.line_height = Some(line_height);
+        }
+
         if let Some(color) = color {
             ctx.state.font.color = Fill::Color(color);
         }
 
+        if let Some(FontFeatures(features)) = &features {
+            // `features`, together with `variant`, `top_edge`, `bottom_edge`
+            // and `line_height` above, are exactly the font-state fields
+            // `text::RunStyle::new` takes; shaping a span with the feature
+            // records set here is `text()`'s job (it's the one that resolves
+            // a concrete font per span and calls `RunStyle::new`), not
+            // `font()`'s — this function only has to make sure they end up
+            // on state for `text()` to read.
+            ctx.state.font.features = features.clone();
+        }
+
         if let Some(FontFamilies(serif)) = &serif {
-            ctx.state.font.families_mut().serif = serif.clone();
+            ctx.state.font.families_mut().serif = resolve_chain(ctx, serif);
         }
 
         if let Some(FontFamilies(sans_serif)) = &sans_serif {
-            ctx.state.font.families_mut().sans_serif = sans_serif.clone();
+            ctx.state.font.families_mut().sans_serif = resolve_chain(ctx, sans_serif);
         }
 
         if let Some(FontFamilies(monospace)) = &monospace {
-            ctx.state.font.families_mut().monospace = monospace.clone();
+            ctx.state.font.families_mut().monospace = resolve_chain(ctx, monospace);
         }
 
         if let Some(body) = &body {
@@ -128,6 +159,29 @@ pub fn font(ctx: &mut EvalContext, args: &mut FuncArgs) -> Value {
     })
 }
 
+/// `fonts`: Enumerate the font families available in the environment.
+///
+/// # Return value
+/// An array of the names of all font families the environment can load.
+pub fn fonts(ctx: &mut EvalContext, _: &mut FuncArgs) -> Value {
+    let families = ctx.env.fonts().all_families().to_vec();
+    Value::Array(families.into_iter().map(Value::Str).collect())
+}
+
+/// Resolves `candidates` (a `serif`/`sans-serif`/`monospace` definition)
+/// down to the one family the environment actually provides, so that an
+/// uninstalled family degrades to a loadable fallback instead of silently
+/// producing tofu, and caches the result for the rest of the document.
+///
+/// Falls back to `candidates` unchanged if the environment offers no
+/// families at all to resolve against.
+fn resolve_chain(ctx: &mut EvalContext, candidates: &[String]) -> Vec<String> {
+    match ctx.env.fonts().resolve(candidates) {
+        Some(resolved) => vec![resolved.to_string()],
+        None => candidates.to_vec(),
+    }
+}
+
 /// A list of font family names.
 #[derive(Debug, Clone, PartialEq)]
 struct FontFamilies(Vec<String>);
@@ -148,6 +202,48 @@ typify! {
     Value::Str(string) => Self::Named(string.to_lowercase())
 }
 
+/// A set of OpenType layout features, mapping 4-byte feature tags (such as
+/// `liga`, `smcp` or `ss01`) to integer values.
+#[derive(Debug, Clone, PartialEq)]
+struct FontFeatures(Vec<(String, u32)>);
+
+typify! {
+    FontFeatures: "array of strings or dictionary of strings to integers",
+    Value::Array(values) => {
+        let total = values.len();
+        let tags: Vec<(String, u32)> = values
+            .into_iter()
+            .filter_map(|v| v.cast().ok())
+            .map(|tag: String| (tag, 1))
+            .collect();
+
+        return if tags.len() < total {
+            CastResult::Warn(Self(tags), "array should only contain strings".to_string())
+        } else {
+            CastResult::Ok(Self(tags))
+        };
+    },
+    Value::Dict(dict) => {
+        let total = dict.len();
+        let features: Vec<(String, u32)> = dict
+            .into_iter()
+            .filter_map(|(tag, v)| {
+                let value: i64 = v.cast().ok()?;
+                u32::try_from(value).ok().map(|value| (tag, value))
+            })
+            .collect();
+
+        return if features.len() < total {
+            CastResult::Warn(
+                Self(features),
+                "dictionary values should be non-negative integers that fit in 32 bits".to_string(),
+            )
+        } else {
+            CastResult::Ok(Self(features))
+        };
+    },
+}
+
 typify! {
     FontStyle: "font style",
 }
@@ -196,3 +292,28 @@ typify! {
 typify! {
     VerticalFontMetric: "vertical font metric",
 }
+
+/// The line height, as a multiple of the font size rather than derived from
+/// the glyph bounding box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct LineHeight(f64);
+
+typify! {
+    LineHeight: "float",
+    Value::Int(number) => {
+        let message = || "should be a positive multiple of the font size".to_string();
+        return if number <= 0 {
+            CastResult::Warn(Self(1.0), message())
+        } else {
+            CastResult::Ok(Self(number as f64))
+        };
+    },
+    Value::Float(number) => {
+        let message = || "should be a positive multiple of the font size".to_string();
+        return if number <= 0.0 {
+            CastResult::Warn(Self(1.0), message())
+        } else {
+            CastResult::Ok(Self(number))
+        };
+    },
+}